@@ -0,0 +1,258 @@
+// vim: sts=4 sw=4 et
+
+/*!
+ Arbitrary value generation, used by `quick_check` to draw test inputs.
+
+ Every type under test implements `Arbitrary`, which knows how to produce
+ an instance of itself from a `Gen` -- a generator that owns both the
+ source of randomness and the current size parameter.
+ */
+
+use std::rand::{Rng, StdRng, SeedableRng};
+use std::hashmap::{HashMap, HashSet};
+use std::hash::Hash;
+
+/// A seeded generator of random values, threaded through every call to
+/// `Arbitrary::arbitrary`.
+///
+/// Keeping the rng here (instead of reaching for `std::rand::random()`
+/// from inside each impl) is what makes a run reproducible: build one
+/// `Gen` from a known seed, and the exact same sequence of values comes
+/// out every time.
+pub struct Gen {
+    priv rng: StdRng,
+    priv size: uint,
+}
+
+impl Gen {
+    /// Create a new `Gen` seeded with `seed`, with `size` as its initial
+    /// size factor.
+    pub fn new(seed: u64, size: uint) -> Gen {
+        let seed_arr = [seed as u32, (seed >> 32) as u32];
+        Gen { rng: SeedableRng::from_seed(seed_arr.as_slice()), size: size }
+    }
+
+    /// The current size factor; `Arbitrary` impls should keep generated
+    /// values (collection lengths, recursion depth, ...) roughly within it.
+    pub fn size(&self) -> uint { self.size }
+
+    /// Replace the size factor, keeping the rng state. `quick_check` calls
+    /// this between trials when `QConfig::grow` is set.
+    pub fn set_size(&mut self, sz: uint) { self.size = sz; }
+
+    /// Draw a value using the underlying rng directly.
+    pub fn gen<T: Rand>(&mut self) -> T { self.rng.gen() }
+
+    /// Draw a value in the half-open range `[lo, hi)`.
+    pub fn gen_range<T: Rand + Ord + Num>(&mut self, lo: T, hi: T) -> T {
+        self.rng.gen_range(lo, hi)
+    }
+}
+
+/// Generate arbitrary values of `Self`, drawing randomness and size from `g`.
+pub trait Arbitrary {
+    fn arbitrary(g: &mut Gen) -> Self;
+}
+
+/// Convenience wrapper so call sites can write `arbitrary::<A>(g)` instead
+/// of `Arbitrary::arbitrary(g)`.
+pub fn arbitrary<A: Arbitrary>(g: &mut Gen) -> A {
+    Arbitrary::arbitrary(g)
+}
+
+impl Arbitrary for () {
+    fn arbitrary(_: &mut Gen) -> () { () }
+}
+
+impl Arbitrary for bool {
+    fn arbitrary(g: &mut Gen) -> bool { g.gen::<u8>() & 1 == 1 }
+}
+
+macro_rules! arbitrary_int_impl(
+    ($t:ty) => (
+        impl Arbitrary for $t {
+            fn arbitrary(g: &mut Gen) -> $t {
+                let sz = g.size() as $t;
+                let lo = if sz == 0 { 0 as $t } else { -sz };
+                let hi = sz + 1;
+                if lo == hi { 0 as $t } else { g.gen_range(lo, hi) }
+            }
+        }
+    )
+)
+
+macro_rules! arbitrary_uint_impl(
+    ($t:ty) => (
+        impl Arbitrary for $t {
+            fn arbitrary(g: &mut Gen) -> $t {
+                let sz = (g.size() + 1) as $t;
+                g.gen_range(0 as $t, sz)
+            }
+        }
+    )
+)
+
+arbitrary_int_impl!(int)
+arbitrary_int_impl!(i8)
+arbitrary_int_impl!(i16)
+arbitrary_int_impl!(i32)
+arbitrary_int_impl!(i64)
+
+arbitrary_uint_impl!(uint)
+arbitrary_uint_impl!(u8)
+arbitrary_uint_impl!(u16)
+arbitrary_uint_impl!(u32)
+arbitrary_uint_impl!(u64)
+
+impl Arbitrary for float {
+    fn arbitrary(g: &mut Gen) -> float {
+        let sz = g.size() as float;
+        g.gen::<float>() * sz
+    }
+}
+
+impl Arbitrary for char {
+    fn arbitrary(g: &mut Gen) -> char {
+        g.gen()
+    }
+}
+
+impl Arbitrary for ~str {
+    fn arbitrary(g: &mut Gen) -> ~str {
+        let n: uint = arbitrary(g);
+        std::str::from_chars(std::vec::from_fn(n, |_| {
+            (g.gen::<u8>() % 95 + 32) as char
+        }))
+    }
+}
+
+impl<T: Arbitrary> Arbitrary for ~[T] {
+    fn arbitrary(g: &mut Gen) -> ~[T] {
+        let n: uint = arbitrary(g);
+        std::vec::from_fn(n, |_| arbitrary(g))
+    }
+}
+
+impl<T: Arbitrary> Arbitrary for Option<T> {
+    fn arbitrary(g: &mut Gen) -> Option<T> {
+        if g.gen::<u8>() % 4 == 0 {
+            None
+        } else {
+            Some(arbitrary(g))
+        }
+    }
+}
+
+macro_rules! arbitrary_tuple_impl(
+    ($($name:ident),+) => (
+        impl<$($name: Arbitrary),+> Arbitrary for ($($name),+,) {
+            fn arbitrary(g: &mut Gen) -> ($($name),+,) {
+                ($(arbitrary::<$name>(g)),+,)
+            }
+        }
+    )
+)
+
+arbitrary_tuple_impl!(A, B)
+arbitrary_tuple_impl!(A, B, C)
+arbitrary_tuple_impl!(A, B, C, D)
+arbitrary_tuple_impl!(A, B, C, D, E)
+arbitrary_tuple_impl!(A, B, C, D, E, F)
+
+impl<K: Arbitrary + Eq + Hash, V: Arbitrary> Arbitrary for HashMap<K, V> {
+    fn arbitrary(g: &mut Gen) -> HashMap<K, V> {
+        let n: uint = arbitrary(g);
+        let mut m = HashMap::new();
+        for std::uint::range(0, n) |_| {
+            m.insert(arbitrary(g), arbitrary(g));
+        }
+        m
+    }
+}
+
+impl<T: Arbitrary + Eq + Hash> Arbitrary for HashSet<T> {
+    fn arbitrary(g: &mut Gen) -> HashSet<T> {
+        let n: uint = arbitrary(g);
+        let mut s = HashSet::new();
+        for std::uint::range(0, n) |_| {
+            s.insert(arbitrary(g));
+        }
+        s
+    }
+}
+
+/// `lo <= hi`, both drawn from the same `Arbitrary` impl as `T`. Useful for
+/// testing code written against a bound pair, e.g. a slicing range.
+#[deriving(Clone, Eq)]
+pub struct IRange<T> {
+    lo: T,
+    hi: T,
+}
+
+impl<T: Arbitrary + Ord> Arbitrary for IRange<T> {
+    fn arbitrary(g: &mut Gen) -> IRange<T> {
+        let a: T = arbitrary(g);
+        let b: T = arbitrary(g);
+        if a <= b { IRange{lo: a, hi: b} } else { IRange{lo: b, hi: a} }
+    }
+}
+
+// No const generics in this era of Rust, so fixed-size arrays only get
+// `Arbitrary` impls spelled out for a handful of small, common lengths --
+// the same approach `std` itself uses for e.g. deriving `Eq` on arrays.
+macro_rules! arbitrary_array_impl(
+    ($n:expr; $($slot:ident),+) => (
+        impl<T: Arbitrary> Arbitrary for [T, ..$n] {
+            fn arbitrary(g: &mut Gen) -> [T, ..$n] {
+                $(let $slot: T = arbitrary(g);)+
+                [$($slot),+]
+            }
+        }
+    )
+)
+
+arbitrary_array_impl!(1; a)
+arbitrary_array_impl!(2; a, b)
+arbitrary_array_impl!(3; a, b, c)
+arbitrary_array_impl!(4; a, b, c, d)
+
+/// A non-negative integer that is generated small (biased towards zero),
+/// useful as a loop bound or index into another generated value.
+///
+/// See `test_qc_smalln` for how it is used.
+#[deriving(Clone, Eq)]
+pub struct SmallN(uint);
+
+impl Arbitrary for SmallN {
+    fn arbitrary(g: &mut Gen) -> SmallN {
+        // Log-uniform over [0, size*size], so small values are most likely.
+        let range = (g.size() * g.size() + 1) as float;
+        let x = g.gen::<float>() * range.ln();
+        SmallN((x.exp() - 1.0) as uint)
+    }
+}
+
+/// A string that is sometimes restricted to ascii and sometimes allowed to
+/// contain arbitrary unicode scalar values, for exercising code that is
+/// only correct for one or the other.
+#[deriving(Clone, Eq)]
+pub struct Unicode(~str);
+
+impl Unicode {
+    pub fn len(&self) -> uint { (**self).len() }
+    pub fn is_ascii(&self) -> bool { (**self).is_ascii() }
+}
+
+impl Arbitrary for Unicode {
+    fn arbitrary(g: &mut Gen) -> Unicode {
+        let n: uint = arbitrary(g);
+        if g.gen::<u8>() % 2 == 0 {
+            // ascii-only
+            Unicode(std::str::from_chars(std::vec::from_fn(n, |_| {
+                (g.gen::<u8>() % 128) as char
+            })))
+        } else {
+            Unicode(std::str::from_chars(std::vec::from_fn(n, |_| g.gen())))
+        }
+    }
+}
@@ -37,9 +37,12 @@ according to those terms.
 
 */
 
+use std::io;
+use std::path::Path;
 use lazy::Lazy;
 use shrink::Shrink;
-use arbitrary::{Arbitrary, arbitrary, SmallN, Unicode};
+use std::hashmap::{HashMap, HashSet};
+use arbitrary::{Arbitrary, arbitrary, Gen, SmallN, Unicode, IRange};
 
 
 mod lazy;
@@ -52,10 +55,20 @@ pub struct QConfig {
     size: uint,
     verbose: bool,
     grow: bool,
+    seed: u64,
+    max_discard: uint,
+    max_shrinks: uint,
+    regression_file: Option<~str>,
 }
 
-/** Default config value */
-pub static config: QConfig = QConfig{ trials: 50, size: 8, verbose: false, grow: true };
+/** Default config value.
+
+ `seed` is 0, which `quick_check` treats as "pick a fresh random seed for
+ this run"; call `.seed(n)` to pin it, e.g. to replay a falsification.
+ */
+pub static config: QConfig = QConfig{
+    trials: 50, size: 8, verbose: false, grow: true, seed: 0, max_discard: 500, max_shrinks: 2000,
+    regression_file: None };
 
 impl QConfig {
     /// Set size factor (default 8)
@@ -74,6 +87,83 @@ impl QConfig {
     pub fn verbose(self, x: bool) -> QConfig {
         QConfig{verbose: x, ..self}
     }
+    /// Pin the seed used to build this run's `Gen` (default: a fresh random
+    /// seed is picked at the start of `quick_check`). Passing the seed
+    /// reported by a falsification reproduces that exact sequence of values.
+    pub fn seed(self, x: u64) -> QConfig {
+        QConfig{seed: x, ..self}
+    }
+    /// Set the number of consecutive `TestResult::Discard`s that `quick_check`
+    /// will tolerate before giving up on the property (default 500, regardless
+    /// of `trials` -- call this explicitly if you want it to track `trials`).
+    pub fn max_discard(self, x: uint) -> QConfig {
+        QConfig{max_discard: x, ..self}
+    }
+    /// Cap the total number of shrink candidates `quick_shrink` will
+    /// evaluate before it stops and returns the best counterexample found
+    /// so far (default 2000). Guards against pathological `Shrink` impls
+    /// that would otherwise recurse without making progress.
+    pub fn max_shrinks(self, x: uint) -> QConfig {
+        QConfig{max_shrinks: x, ..self}
+    }
+    /// Name a file to record falsifying seeds in. When set, `quick_check`
+    /// replays every seed already recorded there before drawing any fresh
+    /// random values, so a failure seen once keeps failing on every
+    /// subsequent run until the property is actually fixed (default: off).
+    pub fn regression_file(self, path: ~str) -> QConfig {
+        QConfig{regression_file: Some(path), ..self}
+    }
+}
+
+/// The outcome of testing a property against one generated value.
+///
+/// Properties that only make sense under some precondition (e.g. "for all
+/// *sorted* vectors...") should return `Discard` rather than folding the
+/// precondition into a boolean -- a `Discard` is re-drawn and does not count
+/// towards `trials`, so it can't be confused with a vacuously-true pass.
+///
+/// `Fail` carries an optional explanatory message, set when the property
+/// came from a `Result<(), ~str>` (see `Testable`) so a shrunk counterexample
+/// can be reported alongside *why* it fails, not just *that* it does.
+pub enum TestResult {
+    Pass,
+    Fail(Option<~str>),
+    Discard,
+}
+
+impl TestResult {
+    /// Build a `TestResult` from a plain boolean property.
+    pub fn from_bool(b: bool) -> TestResult {
+        if b { Pass } else { Fail(None) }
+    }
+    /// A result that doesn't count towards `trials` and isn't retained as
+    /// either a pass or a failure.
+    pub fn discard() -> TestResult { Discard }
+}
+
+/// Converts a property's return value into a `TestResult`. Implemented for
+/// plain `bool` (backwards compatible with pre-`TestResult` properties), for
+/// `TestResult` itself, and for `Result<(), ~str>` so a failing property can
+/// explain itself: `|v: ~[int]| if is_sorted(v) { Ok(()) } else { Err(fmt!("not sorted: %?", v)) }`.
+pub trait Testable {
+    fn result(self) -> TestResult;
+}
+
+impl Testable for bool {
+    fn result(self) -> TestResult { TestResult::from_bool(self) }
+}
+
+impl Testable for TestResult {
+    fn result(self) -> TestResult { self }
+}
+
+impl Testable for Result<(), ~str> {
+    fn result(self) -> TestResult {
+        match self {
+            Ok(()) => Pass,
+            Err(msg) => Fail(Some(msg)),
+        }
+    }
 }
 
 /**
@@ -95,50 +185,213 @@ impl QConfig {
  `quick_check("name", config, |x: Type| property(x));`
 
  `quick_check("str", config.trials(100), |s: ~str| s.is_ascii());`
- 
+
+ `property` may return a plain `bool`, a `TestResult` for conditional
+ properties (`|v: ~[u8]| if v.len() < 2 { TestResult::discard() } else {
+ TestResult::from_bool(is_sorted(sort(v))) }`), or a `Result<(), ~str>` when
+ a shrunk counterexample should carry an explanatory message
+ (`|v: ~[int]| if is_sorted(v) { Ok(()) } else { Err(fmt!("not sorted: %?", v)) }`).
+ A `Discard` doesn't count towards `trials` and is simply re-drawn, up to
+ `QConfig::max_discard` consecutive discards before quick_check gives up on
+ the property.
+
  NOTE: `A` must implement `Clone`.
  */
-pub fn quick_check<A: Owned + Clone + Shrink + Arbitrary>(name: &str, cfg: QConfig, prop: &fn(A) -> bool) {
-    for std::uint::range(0, cfg.trials) |i| {
-        let value = arbitrary::<A>(cfg.size + if cfg.grow { i / 8 } else { 0 });
+pub fn quick_check<A: Owned + Clone + Eq + Shrink + Arbitrary, R: Testable>(
+        name: &str, cfg: QConfig, prop: &fn(A) -> R) {
+    match cfg.regression_file {
+        Some(ref path) => replay_regressions(*path, name, cfg, prop),
+        None => {}
+    }
+
+    let seed = if cfg.seed == 0 { std::rand::random() } else { cfg.seed };
+    match run_trials(cfg, name, seed, prop) {
+        Some((v_copy, trial)) => {
+            if cfg.verbose {
+                println(fmt!("qc %s: first falsification with value '%?'", name, &v_copy));
+            }
+            match cfg.regression_file {
+                Some(ref path) => record_regression(*path, seed, cfg.size),
+                None => {}
+            }
+            let (shrink, steps, msg) = quick_shrink(cfg, v_copy, prop);
+            fail!(fmt!("qc %s: falsified (%u trials, seed %?, %u shrinks) with value '%?'%s",
+                name, 1+trial, seed, steps, shrink, reason_suffix(msg)));
+        }
+        None => {
+            if cfg.verbose {
+                println(fmt!("qc %s: passed'", name));
+            }
+        }
+    }
+}
+
+/// Run up to `cfg.trials` trials seeded from `seed`, following the exact
+/// same size/discard schedule `quick_check` does. Returns the first
+/// falsifying value together with the trial it was found on, or `None` if
+/// every trial passed.
+///
+/// Because this is the one place that schedule is expressed, replaying a
+/// `(seed, size)` recorded by `record_regression` through `run_trials`
+/// again reconstructs the identical sequence of draws -- and so the
+/// identical falsifying value -- rather than just redrawing trial 0.
+fn run_trials<A: Owned + Clone + Arbitrary, R: Testable>(
+        cfg: QConfig, name: &str, seed: u64, prop: &fn(A) -> R) -> Option<(A, uint)> {
+    let mut g = Gen::new(seed, cfg.size);
+    let mut trial = 0u;
+    let mut discards = 0u;
+    while trial < cfg.trials {
+        if cfg.grow { g.set_size(cfg.size + trial / 8); }
+        let value = arbitrary::<A>(&mut g);
         if cfg.verbose {
-            //println(fmt!("qc %s:  %u. trying value '%?'", name, 1+i, &value));
+            //println(fmt!("qc %s:  %u. trying value '%?'", name, 1+trial, &value));
         }
         let v_copy = value.clone();
-        if !prop(value) {
-            if cfg.verbose {
-                println(fmt!("qc %s: first falsification with value '%?'", name, &v_copy));
+        match prop(value).result() {
+            Discard => {
+                discards += 1;
+                if discards > cfg.max_discard {
+                    fail!(fmt!("qc %s: gave up after %u trials (%u consecutive discards, seed %?)",
+                        name, trial, discards, seed));
+                }
+            }
+            Pass => {
+                discards = 0;
+                trial += 1;
+            }
+            Fail(_) => {
+                return Some((v_copy, trial));
             }
-            let shrink = quick_shrink(cfg, v_copy, prop);
-            fail!(fmt!("qc %s: falsified (%u trials) with value '%?'", name, 1+i, shrink));
         }
     }
-    if cfg.verbose {
-        println(fmt!("qc %s: passed'", name));
+    None
+}
+
+/// Re-run `prop` against every `(seed, size)` pair already recorded in
+/// `path`, so a previously-found counterexample is caught immediately and
+/// deterministically, before any fresh random trials run. Replays through
+/// `run_trials` with this run's `cfg` (its `size` replaced by the value
+/// recorded alongside the seed), so when the other settings match the run
+/// that recorded the regression, it reconstructs the exact draw that failed
+/// rather than just draw zero.
+fn replay_regressions<A: Owned + Clone + Eq + Shrink + Arbitrary, R: Testable>(
+        path: &str, name: &str, cfg: QConfig, prop: &fn(A) -> R) {
+    for load_regressions(path).iter().advance |&(rseed, rsize)| {
+        let replay_cfg = cfg.size(rsize);
+        match run_trials(replay_cfg, name, rseed, prop) {
+            Some((v_copy, _)) => {
+                let (shrink, steps, msg) = quick_shrink(cfg, v_copy, prop);
+                fail!(fmt!("qc %s: known regression (seed %?, %u shrinks) with value '%?'%s",
+                    name, rseed, steps, shrink, reason_suffix(msg)));
+            }
+            None => {}
+        }
+    }
+}
+
+/// Parse the `seed,size` lines of a regression file. Missing or unreadable
+/// files are treated as "no known regressions yet".
+fn load_regressions(path: &str) -> ~[(u64, uint)] {
+    let mut seeds = ~[];
+    match io::file_reader(&Path::new(path)) {
+        Ok(rdr) => {
+            while !rdr.eof() {
+                let line = rdr.read_line();
+                let parts: ~[&str] = line.trim().split_iter(',').collect();
+                if parts.len() == 2 {
+                    match (std::u64::from_str(parts[0]), std::uint::from_str(parts[1])) {
+                        (Some(seed), Some(size)) => seeds.push((seed, size)),
+                        _ => {}
+                    }
+                }
+            }
+        }
+        Err(_) => {}
+    }
+    seeds
+}
+
+/// Append a falsifying `(seed, size)` to the regression file, creating it
+/// if necessary. Earlier entries (including fixed ones) are kept, since
+/// re-checking a fixed seed is cheap and guards against it regressing again.
+fn record_regression(path: &str, seed: u64, size: uint) {
+    match io::file_writer(&Path::new(path), [io::Append, io::Create]) {
+        Ok(wr) => wr.write_line(fmt!("%?,%u", seed, size)),
+        Err(_) => {}
     }
 }
 
-pub fn quick_shrink<A: Owned + Clone + Shrink + Arbitrary>(cfg: QConfig, value: A, prop: &fn(A) -> bool) -> A {
-    //assert!(!prop(value.clone()));
-    let mut shrinks = value.shrink();
-    for shrinks.advance |elt| {
-        let elt_cpy = elt.clone();
-        if !prop(elt) {
-            if cfg.verbose { println(fmt!("Shrunk to: %?", &elt_cpy)); }
-            return quick_shrink(cfg, elt_cpy, prop);
+/// Shrink `value` (a known counterexample to `prop`) towards a smaller one.
+///
+/// Walks `value.shrink()` looking for a still-failing candidate, and when
+/// found repeats the process from there. Candidates equal (by `Eq`) to their
+/// parent are skipped, and the `QConfig::max_shrinks` budget on the total
+/// number of candidates examined bounds the work, so shrinking stops even
+/// for degenerate `Shrink` impls that cycle between values.
+///
+/// Returns the best counterexample found, the number of successful shrink
+/// steps taken to reach it, and the explanatory message from the last
+/// `Fail` seen, starting with `value` itself (if `prop` returns
+/// `Result<(), ~str>`).
+pub fn quick_shrink<A: Owned + Clone + Eq + Shrink + Arbitrary, R: Testable>(
+        cfg: QConfig, value: A, prop: &fn(A) -> R) -> (A, uint, Option<~str>) {
+    let mut msg = match prop(value.clone()).result() {
+        Fail(m) => m,
+        Pass | Discard => None,
+    };
+    let mut best = value;
+    let mut steps = 0u;
+    let mut examined = 0u;
+    loop {
+        if examined >= cfg.max_shrinks { break; }
+        let mut shrinks = best.shrink();
+        let mut smaller = None;
+        for shrinks.advance |elt| {
+            if examined >= cfg.max_shrinks { break; }
+            if elt == best { continue; }
+            examined += 1;
+            let elt_cpy = elt.clone();
+            match prop(elt).result() {
+                Fail(m) => {
+                    smaller = Some(elt_cpy);
+                    msg = m;
+                    break;
+                }
+                Pass | Discard => {}
+            }
+        }
+        match smaller {
+            Some(next) => {
+                best = next;
+                steps += 1;
+                if cfg.verbose { println(fmt!("Shrunk to: %?", &best)); }
+            }
+            None => break,
         }
     }
     if cfg.verbose {
-        println(fmt!("Shrink finished: %?", &value));
+        println(fmt!("Shrink finished after %u steps (%u candidates examined): %?", steps, examined, &best));
+    }
+    (best, steps, msg)
+}
+
+/// Format a `Fail` message (if any) as a trailing `" -- msg"` clause for
+/// `fail!()` output.
+fn reason_suffix(msg: Option<~str>) -> ~str {
+    match msg {
+        Some(m) => fmt!(" -- %s", m),
+        None => ~"",
     }
-    value
 }
 
 pub fn quick_check_occurs<A: Arbitrary>(cfg: QConfig, name: &str, prop: &fn(A) -> bool) {
+    let seed = if cfg.seed == 0 { std::rand::random() } else { cfg.seed };
+    let mut g = Gen::new(seed, cfg.size);
     let mut n = 0u;
     for std::uint::range(0, cfg.trials) |i| {
         n += 1;
-        let value = arbitrary(cfg.size + if cfg.grow { i / 8 } else { 0 });
+        if cfg.grow { g.set_size(cfg.size + i / 8); }
+        let value = arbitrary(&mut g);
         if prop(value) {
             if cfg.verbose {
                 println(fmt!("qc %s: occured (%u trials)", name, n));
@@ -147,7 +400,7 @@ pub fn quick_check_occurs<A: Arbitrary>(cfg: QConfig, name: &str, prop: &fn(A) -
         }
     }
     if n >= cfg.trials {
-        fail!(fmt!("qc %s: could not to reproduce", name));
+        fail!(fmt!("qc %s: could not to reproduce (seed %?)", name, seed));
     }
 }
 
@@ -174,7 +427,7 @@ pub macro_rules! quick_check_occurs(
 )
 
 /// Example of how to implement Arbitrary
-#[deriving(Clone)]
+#[deriving(Clone, Eq)]
 enum UserType<T> {
     Nothing,
     Blob(int, ~str),
@@ -182,12 +435,12 @@ enum UserType<T> {
 }
 
 impl<T: Clone + Arbitrary> Arbitrary for UserType<T> {
-    fn arbitrary(sz: uint) -> UserType<T> {
-        let x: u8 = std::rand::random();
+    fn arbitrary(g: &mut Gen) -> UserType<T> {
+        let x: u8 = g.gen();
         match x % 3 {
             0 => Nothing,
-            1 => Blob(arbitrary(sz), arbitrary(sz)),
-            _ => Blub(arbitrary(sz)),
+            1 => Blob(arbitrary(g), arbitrary(g)),
+            _ => Blub(arbitrary(g)),
         }
     }
 
@@ -202,19 +455,25 @@ impl Shrink for SmallN {
 }
 
 /// Example of how to implement Arbitrary and Shrink
-#[deriving(Clone)]
+#[deriving(Clone, Eq)]
 enum UserTree<T> {
     Nil,
     Node(T, ~UserTree<T>, ~UserTree<T>)
 }
 
 impl<T: Clone + Arbitrary> Arbitrary for UserTree<T> {
-    fn arbitrary(sz: uint) -> UserTree<T> {
-        let rint: u8 = std::rand::random();
-        if sz == 0 || rint % 4 == 0 {
+    fn arbitrary(g: &mut Gen) -> UserTree<T> {
+        let rint: u8 = g.gen();
+        if g.size() == 0 || rint % 4 == 0 {
             Nil
         } else {
-            Node(arbitrary(sz), ~arbitrary(sz/2), ~arbitrary(sz/2))
+            let sz = g.size();
+            let x = arbitrary(g);
+            g.set_size(sz / 2);
+            let l = ~arbitrary(g);
+            let r = ~arbitrary(g);
+            g.set_size(sz);
+            Node(x, l, r)
         }
     }
 }
@@ -284,64 +543,94 @@ fn test_qc_smalln() {
     quick_check_occurs!(|n: SmallN| *n > 10);
 }
 
+#[test]
+fn test_qc_regression_file() {
+    let path = ~"test_qc_regression_roundtrip.tmp";
+    std::os::remove_file(&Path::new(path));
+
+    // Populate the regression file through the real pipeline: a failing
+    // quick_check run records its seed before it fails.
+    let recorded = do std::task::try {
+        quick_check("regression seed", config.regression_file(path.clone()).trials(5),
+            |_: int| false);
+    };
+    assert!(recorded.is_err());
+
+    let seeds = load_regressions(path);
+    assert_eq!(seeds.len(), 1);
+
+    // Replaying that recorded seed must keep drawing until it reaches the
+    // value that actually failed, not just re-check the very first draw --
+    // this only fails on the 3rd call, which a one-shot single-draw replay
+    // (the old behavior) would silently pass straight through.
+    let mut n = 0;
+    let replayed = do std::task::try {
+        replay_regressions(path, "regression replay", config.trials(10), |_: int| { n += 1; n != 3 });
+    };
+    assert!(replayed.is_err());
+
+    std::os::remove_file(&Path::new(path));
+}
+
 #[test]
 fn test_qc_shrink() {
     /* Test minimal shrinks with false props */
     let v = SmallN(100);
-    let shrink = quick_shrink(config, v, |_| false);
+    let (shrink, _, _) = quick_shrink(config, v, |_| false);
     assert_eq!(*shrink, 0);
 
     let v = 20000000u;
-    let shrink = quick_shrink(config, v, |x| x < 1200301);
+    let (shrink, _, _) = quick_shrink(config, v, |x| x < 1200301);
     assert_eq!(shrink, 1200301);
 
     let s = ~[0, 1, 1, 2, 1, 0, 1, 0, 1];
-    let shrink = quick_shrink(config, s, |_| false);
+    let (shrink, _, _) = quick_shrink(config, s, |_| false);
     assert_eq!(shrink, ~[]);
 
     /* Make sure we can shrink nested containers */
     let v = Some(~[Some(~"hi"), None, Some(~""), Some(~"long text from me")]);
-    let shrink = quick_shrink(config, v, |_| false);
+    let (shrink, _, _) = quick_shrink(config, v, |_| false);
     assert_eq!(shrink, None);
 
     let s = ~[Some(~"hi"), None, Some(~"more"), None];
-    assert_eq!(quick_shrink(config, s, |v| !v.iter().filter_map(|&x| x).any_(|s| s.contains_char('e'))),
-        ~[Some(~"e")]);
+    let (shrink, _, _) = quick_shrink(config, s, |v| !v.iter().filter_map(|&x| x).any_(|s| s.contains_char('e')));
+    assert_eq!(shrink, ~[Some(~"e")]);
 
     let s = ~"boots are made for walking";
-    assert_eq!(quick_shrink(config, s, |v| v.iter().count(|x| x == 'a') <= 1),
-        ~"aa");
+    let (shrink, _, _) = quick_shrink(config, s, |v| v.iter().count(|x| x == 'a') <= 1);
+    assert_eq!(shrink, ~"aa");
 
     let s = ~[0, 1, 1, 2, 1, 0, 1, 0, 1];
     let sum = |v: ~[int]| v.iter().fold(0, |a, &b| a + b);
-    let shrink = quick_shrink(config, s, |v| sum(v) < 3);
+    let (shrink, _, _) = quick_shrink(config, s, |v| sum(v) < 3);
     assert_eq!(sum(shrink), 3);
 
     let s = (~"more meat", ~"beef");
-    let shrink = quick_shrink(config, s, |(a, b)| !(a.contains_char('e') && b.contains_char('e')));
+    let (shrink, _, _) = quick_shrink(config, s, |(a, b)| !(a.contains_char('e') && b.contains_char('e')));
     assert_eq!(shrink, (~"e", ~"e"));
 
     let s = (SmallN(1), SmallN(10), SmallN(3));
-    let shrink = quick_shrink(config, s, |(a, b, c)| *a + *b + *c == 0);
+    let (shrink, _, _) = quick_shrink(config, s, |(a, b, c)| *a + *b + *c == 0);
     assert_eq!(shrink, (SmallN(0), SmallN(0), SmallN(1)));
 
     /* test the biggest supported tuple */
-    let t: (uint, (), ~[u8], Option<bool>, u8, ~str) = arbitrary(config.size);
-    let shrink = quick_shrink(config, t, |_| false);
+    let mut g = Gen::new(1, config.size);
+    let t: (uint, (), ~[u8], Option<bool>, u8, ~str) = arbitrary(&mut g);
+    let (shrink, _, _) = quick_shrink(config, t, |_| false);
     assert_eq!(shrink, (0, (), ~[], None, 0, ~""));
 }
 
 #[test]
 #[should_fail]
 fn test_qc_tree() {
+    /* Used to crash: quick_shrink recursed on UserTree's Shrink impl with
+     * no bound on depth or progress. max_shrinks + the skip of candidates
+     * equal to their parent in quick_shrink now guarantee this terminates. */
     quick_check!(config.size(7),
         |u: UserTree<u8>| match u {
             Node(x, ~Node(y, _, _), ~Nil) => (x ^ y) & 0x13 == 0,
             _ => true,
         });
-    /* crashing..
-    fail!("missing test");
-    */
 }
 
 #[test]
@@ -351,6 +640,22 @@ fn test_qc_shrink_fail() {
         |(a, b): (~str, ~str)| !(a.contains_char('e') || b.contains_char('e')));
 }
 
+#[test]
+fn test_qc_testable_result() {
+    /* Ok(())/Err(msg) properties behave like true/false, and the message
+     * is available to quick_shrink for reporting. */
+    quick_check!(|v: ~[int]| -> Result<(), ~str> {
+        if v.len() < 1000 { Ok(()) } else { Err(fmt!("too long: %u", v.len())) }
+    });
+}
+
+#[test]
+#[should_fail]
+fn test_qc_testable_result_fail() {
+    quick_check!(|v: ~[u8]|
+        if v.len() <= 3 { Ok(()) } else { Err(fmt!("too long: %?", v)) });
+}
+
 
 #[deriving(Rand, Clone)]
 struct Test_Foo { x: float, u: int }
@@ -378,6 +683,45 @@ fn test_qc_containers() {
     quick_check_occurs!(|s: Unicode| !s.is_ascii());
 }
 
+#[test]
+fn test_qc_map_set_range() {
+    quick_check_occurs!(|m: HashMap<u8, u8>| m.len() == 0);
+    quick_check_occurs!(|m: HashMap<u8, u8>| m.len() > 3);
+    quick_check!(|m: HashMap<u8, u8>| m.iter().all(|(k, _)| m.contains_key(k)));
+
+    quick_check_occurs!(|s: HashSet<u8>| s.len() == 0);
+    quick_check_occurs!(|s: HashSet<u8>| s.len() > 3);
+
+    quick_check!(|r: IRange<int>| r.lo <= r.hi);
+
+    quick_check!(|a: [u8, ..4]| a.len() == 4);
+}
+
+#[test]
+fn test_qc_shrink_containers() {
+    let mut m = HashMap::new();
+    m.insert(1u8, 5u8);
+    m.insert(2u8, 7u8);
+    let (shrink, _, _) = quick_shrink(config, m, |_| false);
+    assert_eq!(shrink, HashMap::new());
+
+    let mut s = HashSet::new();
+    s.insert(1u8);
+    s.insert(2u8);
+    s.insert(3u8);
+    let (shrink, _, _) = quick_shrink(config, s, |_| false);
+    assert_eq!(shrink, HashSet::new());
+
+    /* lo is left alone; hi shrinks down towards it */
+    let r = IRange{lo: 3, hi: 50};
+    let (shrink, _, _) = quick_shrink(config, r, |r| r.hi - r.lo >= 1);
+    assert_eq!(shrink, IRange{lo: 3, hi: 3});
+
+    let a: [int, ..4] = [5, 10, 15, 20];
+    let (shrink, _, _) = quick_shrink(config, a, |_| false);
+    assert_eq!(shrink, [0, 0, 0, 0]);
+}
+
 #[test]
 #[should_fail]
 fn test_invalid_utf8() {
@@ -0,0 +1,344 @@
+// vim: sts=4 sw=4 et
+
+/*!
+ Shrinking: given a counterexample, lazily produce smaller candidates that
+ `quick_shrink` can try in its place.
+
+ Each `shrink()` call should return candidates ordered roughly smallest
+ (most aggressive) first, since `quick_shrink` takes the first one that
+ still falsifies the property.
+ */
+
+use lazy::Lazy;
+use std::hashmap::{HashMap, HashSet};
+use std::hash::Hash;
+use arbitrary::IRange;
+
+pub trait Shrink {
+    fn shrink(&self) -> Lazy<Self>;
+}
+
+impl Shrink for () {
+    fn shrink(&self) -> Lazy<()> { Lazy::new() }
+}
+
+impl Shrink for bool {
+    fn shrink(&self) -> Lazy<bool> {
+        do Lazy::create |L| {
+            if *self { L.push(false); }
+        }
+    }
+}
+
+macro_rules! shrink_int_impl(
+    ($t:ty) => (
+        impl Shrink for $t {
+            fn shrink(&self) -> Lazy<$t> {
+                let x = *self;
+                do Lazy::create |L| {
+                    if x != 0 as $t {
+                        L.push(0 as $t);
+                        let mut i = x;
+                        while i != 0 as $t {
+                            i = i / 2 as $t;
+                            let cand = x - i;
+                            if cand != x { L.push(cand); }
+                        }
+                    }
+                }
+            }
+        }
+    )
+)
+
+shrink_int_impl!(int)
+shrink_int_impl!(i8)
+shrink_int_impl!(i16)
+shrink_int_impl!(i32)
+shrink_int_impl!(i64)
+shrink_int_impl!(uint)
+shrink_int_impl!(u8)
+shrink_int_impl!(u16)
+shrink_int_impl!(u32)
+shrink_int_impl!(u64)
+
+impl Shrink for float {
+    fn shrink(&self) -> Lazy<float> {
+        let x = *self;
+        do Lazy::create |L| {
+            if x != 0.0 {
+                L.push(0.0);
+                L.push(x / 2.0);
+            }
+        }
+    }
+}
+
+impl Shrink for char {
+    fn shrink(&self) -> Lazy<char> {
+        let x = *self;
+        do Lazy::create |L| {
+            if x != 'a' { L.push('a'); }
+        }
+    }
+}
+
+impl Shrink for ~str {
+    fn shrink(&self) -> Lazy<~str> {
+        let s = self.clone();
+        do Lazy::create |L| {
+            if s.len() > 0 {
+                L.push(~"");
+                let chars: ~[char] = s.iter().collect();
+                for i in std::uint::range(0, chars.len()) {
+                    let without: ~[char] = chars.iter().enumerate()
+                        .filter_map(|(j, &c)| if j == i { None } else { Some(c) })
+                        .collect();
+                    L.push(std::str::from_chars(without));
+                }
+            }
+        }
+    }
+}
+
+impl<T: Clone + Shrink + Owned> Shrink for ~[T] {
+    fn shrink(&self) -> Lazy<~[T]> {
+        let v = self.clone();
+        do Lazy::create |L| {
+            if v.len() > 0 {
+                L.push(~[]);
+                for i in std::uint::range(0, v.len()) {
+                    let without: ~[T] = v.iter().enumerate()
+                        .filter_map(|(j, x)| if j == i { None } else { Some(x.clone()) })
+                        .collect();
+                    L.push(without);
+                }
+                for i in std::uint::range(0, v.len()) {
+                    let rest = v.clone();
+                    L.push_map_env(v[i].shrink(), (i, rest), |x, &(i, ref rest)| {
+                        let mut out = rest.clone();
+                        out[i] = x;
+                        out
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl<T: Clone + Shrink + Owned> Shrink for ~T {
+    fn shrink(&self) -> Lazy<~T> {
+        do Lazy::create |L| {
+            L.push_map((**self).shrink(), |x| ~x);
+        }
+    }
+}
+
+impl<T: Clone + Shrink + Owned> Shrink for Option<T> {
+    fn shrink(&self) -> Lazy<Option<T>> {
+        let v = self.clone();
+        do Lazy::create |L| {
+            match v {
+                None => {}
+                Some(x) => {
+                    L.push(None);
+                    L.push_map(x.shrink(), |y| Some(y));
+                }
+            }
+        }
+    }
+}
+
+impl<A: Clone + Shrink + Owned, B: Clone + Shrink + Owned> Shrink for (A, B) {
+    fn shrink(&self) -> Lazy<(A, B)> {
+        let (a, b) = self.clone();
+        do Lazy::create |L| {
+            L.push_map_env(a.shrink(), b.clone(), |x, b| (x, (*b).clone()));
+            L.push_map_env(b.shrink(), a.clone(), |y, a| ((*a).clone(), y));
+        }
+    }
+}
+
+impl<A: Clone + Shrink + Owned, B: Clone + Shrink + Owned, C: Clone + Shrink + Owned>
+        Shrink for (A, B, C) {
+    fn shrink(&self) -> Lazy<(A, B, C)> {
+        let (a, b, c) = self.clone();
+        do Lazy::create |L| {
+            L.push_map_env(a.shrink(), (b.clone(), c.clone()), |x, &(ref b, ref c)| (x, b.clone(), c.clone()));
+            L.push_map_env(b.shrink(), (a.clone(), c.clone()), |y, &(ref a, ref c)| (a.clone(), y, c.clone()));
+            L.push_map_env(c.shrink(), (a.clone(), b.clone()), |z, &(ref a, ref b)| (a.clone(), b.clone(), z));
+        }
+    }
+}
+
+impl<A: Clone + Shrink + Owned, B: Clone + Shrink + Owned, C: Clone + Shrink + Owned,
+     D: Clone + Shrink + Owned>
+        Shrink for (A, B, C, D) {
+    fn shrink(&self) -> Lazy<(A, B, C, D)> {
+        let (a, b, c, d) = self.clone();
+        do Lazy::create |L| {
+            L.push_map_env(a.shrink(), (b.clone(), c.clone(), d.clone()),
+                |x, &(ref b, ref c, ref d)| (x, b.clone(), c.clone(), d.clone()));
+            L.push_map_env(b.shrink(), (a.clone(), c.clone(), d.clone()),
+                |y, &(ref a, ref c, ref d)| (a.clone(), y, c.clone(), d.clone()));
+            L.push_map_env(c.shrink(), (a.clone(), b.clone(), d.clone()),
+                |z, &(ref a, ref b, ref d)| (a.clone(), b.clone(), z, d.clone()));
+            L.push_map_env(d.shrink(), (a.clone(), b.clone(), c.clone()),
+                |w, &(ref a, ref b, ref c)| (a.clone(), b.clone(), c.clone(), w));
+        }
+    }
+}
+
+impl<A: Clone + Shrink + Owned, B: Clone + Shrink + Owned, C: Clone + Shrink + Owned,
+     D: Clone + Shrink + Owned, E: Clone + Shrink + Owned>
+        Shrink for (A, B, C, D, E) {
+    fn shrink(&self) -> Lazy<(A, B, C, D, E)> {
+        let (a, b, c, d, e) = self.clone();
+        do Lazy::create |L| {
+            L.push_map_env(a.shrink(), (b.clone(), c.clone(), d.clone(), e.clone()),
+                |x, &(ref b, ref c, ref d, ref e)| (x, b.clone(), c.clone(), d.clone(), e.clone()));
+            L.push_map_env(b.shrink(), (a.clone(), c.clone(), d.clone(), e.clone()),
+                |y, &(ref a, ref c, ref d, ref e)| (a.clone(), y, c.clone(), d.clone(), e.clone()));
+            L.push_map_env(c.shrink(), (a.clone(), b.clone(), d.clone(), e.clone()),
+                |z, &(ref a, ref b, ref d, ref e)| (a.clone(), b.clone(), z, d.clone(), e.clone()));
+            L.push_map_env(d.shrink(), (a.clone(), b.clone(), c.clone(), e.clone()),
+                |w, &(ref a, ref b, ref c, ref e)| (a.clone(), b.clone(), c.clone(), w, e.clone()));
+            L.push_map_env(e.shrink(), (a.clone(), b.clone(), c.clone(), d.clone()),
+                |v, &(ref a, ref b, ref c, ref d)| (a.clone(), b.clone(), c.clone(), d.clone(), v));
+        }
+    }
+}
+
+impl<A: Clone + Shrink + Owned, B: Clone + Shrink + Owned, C: Clone + Shrink + Owned,
+     D: Clone + Shrink + Owned, E: Clone + Shrink + Owned, F: Clone + Shrink + Owned>
+        Shrink for (A, B, C, D, E, F) {
+    fn shrink(&self) -> Lazy<(A, B, C, D, E, F)> {
+        let (a, b, c, d, e, f) = self.clone();
+        do Lazy::create |L| {
+            L.push_map_env(a.shrink(), (b.clone(), c.clone(), d.clone(), e.clone(), f.clone()),
+                |x, &(ref b, ref c, ref d, ref e, ref f)| (x, b.clone(), c.clone(), d.clone(), e.clone(), f.clone()));
+            L.push_map_env(b.shrink(), (a.clone(), c.clone(), d.clone(), e.clone(), f.clone()),
+                |y, &(ref a, ref c, ref d, ref e, ref f)| (a.clone(), y, c.clone(), d.clone(), e.clone(), f.clone()));
+            L.push_map_env(c.shrink(), (a.clone(), b.clone(), d.clone(), e.clone(), f.clone()),
+                |z, &(ref a, ref b, ref d, ref e, ref f)| (a.clone(), b.clone(), z, d.clone(), e.clone(), f.clone()));
+            L.push_map_env(d.shrink(), (a.clone(), b.clone(), c.clone(), e.clone(), f.clone()),
+                |w, &(ref a, ref b, ref c, ref e, ref f)| (a.clone(), b.clone(), c.clone(), w, e.clone(), f.clone()));
+            L.push_map_env(e.shrink(), (a.clone(), b.clone(), c.clone(), d.clone(), f.clone()),
+                |v, &(ref a, ref b, ref c, ref d, ref f)| (a.clone(), b.clone(), c.clone(), d.clone(), v, f.clone()));
+            L.push_map_env(f.shrink(), (a.clone(), b.clone(), c.clone(), d.clone(), e.clone()),
+                |u, &(ref a, ref b, ref c, ref d, ref e)| (a.clone(), b.clone(), c.clone(), d.clone(), e.clone(), u));
+        }
+    }
+}
+
+impl<K: Clone + Shrink + Eq + Hash + Owned, V: Clone + Shrink + Owned> Shrink for HashMap<K, V> {
+    fn shrink(&self) -> Lazy<HashMap<K, V>> {
+        let entries: ~[(K, V)] = self.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        do Lazy::create |L| {
+            if entries.len() > 0 {
+                L.push(HashMap::new());
+                // Drop one entry at a time, building each smaller map up
+                // front (same eager style ~[T] already uses).
+                for i in std::uint::range(0, entries.len()) {
+                    let without: HashMap<K, V> = entries.iter().enumerate()
+                        .filter_map(|(j, e)| if j == i { None } else { Some(e.clone()) })
+                        .collect();
+                    L.push(without);
+                }
+                // Then shrink one remaining entry's key or value in place,
+                // holding the rest fixed -- mirrors ~[T]'s "shrink an
+                // element" step, so an oversized key or value can still
+                // minimize once dropping entries alone isn't enough.
+                for i in std::uint::range(0, entries.len()) {
+                    let (k, v) = entries[i].clone();
+                    L.push_map_env(k.shrink(), (i, entries.clone()), |k2, &(i, ref entries)| {
+                        let (_, ref v) = entries[i];
+                        let mut m: HashMap<K, V> = entries.iter().enumerate()
+                            .filter_map(|(j, e)| if j == i { None } else { Some(e.clone()) })
+                            .collect();
+                        m.insert(k2, (*v).clone());
+                        m
+                    });
+                    L.push_map_env(v.shrink(), (i, entries.clone()), |v2, &(i, ref entries)| {
+                        let (ref k, _) = entries[i];
+                        let mut m: HashMap<K, V> = entries.iter().enumerate()
+                            .filter_map(|(j, e)| if j == i { None } else { Some(e.clone()) })
+                            .collect();
+                        m.insert((*k).clone(), v2);
+                        m
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl<T: Clone + Shrink + Eq + Hash + Owned> Shrink for HashSet<T> {
+    fn shrink(&self) -> Lazy<HashSet<T>> {
+        let entries: ~[T] = self.iter().map(|x| x.clone()).collect();
+        do Lazy::create |L| {
+            if entries.len() > 0 {
+                L.push(HashSet::new());
+                // Drop one entry at a time, building each smaller set up
+                // front (same eager style ~[T] already uses).
+                for i in std::uint::range(0, entries.len()) {
+                    let without: HashSet<T> = entries.iter().enumerate()
+                        .filter_map(|(j, x)| if j == i { None } else { Some(x.clone()) })
+                        .collect();
+                    L.push(without);
+                }
+                // Then shrink one remaining element in place, holding the
+                // rest fixed -- mirrors ~[T]'s "shrink an element" step.
+                for i in std::uint::range(0, entries.len()) {
+                    L.push_map_env(entries[i].shrink(), (i, entries.clone()), |x2, &(i, ref rest)| {
+                        let mut s: HashSet<T> = rest.iter().enumerate()
+                            .filter_map(|(j, x)| if j == i { None } else { Some(x.clone()) })
+                            .collect();
+                        s.insert(x2);
+                        s
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Shrinks by moving `hi` towards `lo`; `lo` itself is left alone so the
+/// result is always still a valid (`lo <= hi`) range.
+impl<T: Clone + Shrink + Ord + Owned> Shrink for IRange<T> {
+    fn shrink(&self) -> Lazy<IRange<T>> {
+        let lo = self.lo.clone();
+        let hi = self.hi.clone();
+        do Lazy::create |L| {
+            L.push_map_env(hi.shrink(), lo.clone(), |h, lo| {
+                let h = if h < *lo { (*lo).clone() } else { h };
+                IRange{lo: (*lo).clone(), hi: h}
+            });
+        }
+    }
+}
+
+macro_rules! shrink_array_impl(
+    ($n:expr; $($idx:expr),+) => (
+        impl<T: Clone + Shrink + Owned> Shrink for [T, ..$n] {
+            fn shrink(&self) -> Lazy<[T, ..$n]> {
+                let a = self.clone();
+                do Lazy::create |L| {
+                    $(
+                        let rest = a.clone();
+                        L.push_map_env(a[$idx].shrink(), rest, |x, rest| {
+                            let mut out = (*rest).clone();
+                            out[$idx] = x;
+                            out
+                        });
+                    )+
+                }
+            }
+        }
+    )
+)
+
+shrink_array_impl!(1; 0)
+shrink_array_impl!(2; 0, 1)
+shrink_array_impl!(3; 0, 1, 2)
+shrink_array_impl!(4; 0, 1, 2, 3)